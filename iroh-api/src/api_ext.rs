@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use crate::{AddEvent, Api, Cid, IpfsPath, OutType};
+use crate::{Api, Cid, IpfsPath, OutType};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use futures::stream::LocalBoxStream;
@@ -9,6 +9,41 @@ use futures::StreamExt;
 use futures::TryStreamExt;
 use relative_path::RelativePathBuf;
 
+#[cfg(all(unix, feature = "fuse"))]
+use crate::fuse::IpfsFuse;
+use crate::search::{search_contents, search_path_name, SearchMatch, SearchQuery, SearchTarget};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// An event emitted while adding a file or directory tree, from
+/// `ApiExt::add_stream` or `ApiExt::add_watch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddEvent {
+    /// A new root CID was computed for (part of) the tree being added.
+    ProgressDelta { cid: Cid, size: Option<u64> },
+    /// `path` was removed from disk and is no longer part of the tree,
+    /// emitted only by `add_watch`.
+    Removed { path: PathBuf },
+}
+
+/// How `ApiExt::get` should behave when `output_path` already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GetMode {
+    /// Error out if the output path already exists. The original, and
+    /// still default, behavior.
+    #[default]
+    Fresh,
+    /// Resume an interrupted `get`: entries already complete on disk are
+    /// left untouched, and a partially-written file is rewritten only from
+    /// the first chunk that differs from the incoming content. Each file's
+    /// incoming content is still read in full to compare it with what is
+    /// on disk.
+    Resume,
+    /// Overwrite what is already at the output path. Whatever sits where a
+    /// file or symlink goes is removed first. A directory entry reuses an
+    /// existing directory; a non-directory in its place makes the `get` fail.
+    Overwrite,
+}
+
 #[async_trait(?Send)]
 pub trait ApiExt: Api {
     /// High level get, equivalent of CLI `iroh get`
@@ -16,22 +51,44 @@ pub trait ApiExt: Api {
         &self,
         ipfs_path: &IpfsPath,
         output_path: Option<&'a Path>,
+        mode: GetMode,
     ) -> Result<PathBuf> {
         if ipfs_path.cid().is_none() {
             return Err(anyhow!("IPFS path does not refer to a CID"));
         }
         let root_path = get_root_path(ipfs_path, output_path);
-        if root_path.exists() {
+        if mode == GetMode::Fresh && root_path.exists() {
             return Err(anyhow!(
                 "output path {} already exists",
                 root_path.display()
             ));
         }
         let blocks = self.get_stream(ipfs_path);
-        save_get_stream(&root_path, blocks).await?;
+        save_get_stream(&root_path, blocks, mode).await?;
         Ok(root_path)
     }
 
+    /// Mount the UnixFS DAG rooted at `ipfs_path` at `mountpoint` as a
+    /// read-only FUSE filesystem, fetching blocks lazily as entries are
+    /// looked up and read rather than eagerly downloading the whole tree.
+    ///
+    /// Blocks the calling task until the filesystem is unmounted.
+    #[cfg(all(unix, feature = "fuse"))]
+    async fn mount(&self, ipfs_path: &IpfsPath, mountpoint: &Path) -> Result<()>
+    where
+        Self: Clone + Send + Sync + 'static,
+    {
+        if ipfs_path.cid().is_none() {
+            return Err(anyhow!("IPFS path does not refer to a CID"));
+        }
+        let api = self.clone();
+        let ipfs_path = ipfs_path.clone();
+        let mountpoint = mountpoint.to_path_buf();
+        tokio::task::spawn_blocking(move || IpfsFuse::mount(api, ipfs_path, &mountpoint))
+            .await
+            .context("fuse mount task panicked")?
+    }
+
     async fn add_stream(
         &self,
         path: &Path,
@@ -52,14 +109,149 @@ pub trait ApiExt: Api {
         let add_events = self.add_stream(path, wrap).await?;
 
         add_events
-            .try_fold(None, |_acc, add_event| async move {
+            .try_fold(None, |acc, add_event| async move {
                 match add_event {
                     AddEvent::ProgressDelta { cid, .. } => Ok(Some(cid)),
+                    AddEvent::Removed { .. } => Ok(acc),
                 }
             })
             .await?
             .context("No cid found")
     }
+
+    /// Watch `path` for changes and keep re-adding it as it's modified on
+    /// disk, yielding a new [`AddEvent`] for every affected entry.
+    ///
+    /// Runs until the returned stream is dropped. A burst of writes to the
+    /// same file is debounced into a single re-add.
+    async fn add_watch(
+        &self,
+        path: &Path,
+        wrap: bool,
+    ) -> Result<LocalBoxStream<'static, Result<AddEvent>>>
+    where
+        Self: Clone + Send + Sync + 'static,
+    {
+        let (watcher, mut changes) = crate::watch::watch(path)?;
+        let api = self.clone();
+
+        let stream = async_stream::try_stream! {
+            // keep the watcher alive for as long as the stream is polled
+            let _watcher = watcher;
+            while let Some(change) = changes.recv().await {
+                match change.kind {
+                    crate::watch::ChangeKind::Removed => {
+                        yield AddEvent::Removed { path: change.path };
+                    }
+                    crate::watch::ChangeKind::Created
+                    | crate::watch::ChangeKind::Modified
+                    | crate::watch::ChangeKind::Renamed => {
+                        if !change.path.exists() {
+                            yield AddEvent::Removed { path: change.path };
+                            continue;
+                        }
+                        let mut events = api.add_stream(&change.path, wrap).await?;
+                        while let Some(event) = events.next().await {
+                            yield event?;
+                        }
+                    }
+                }
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    /// Stream grep-like matches for `query` out of the UnixFS tree at
+    /// `ipfs_path`, without writing any of it to disk.
+    ///
+    /// Directories and symlinks only ever contribute path-name matches;
+    /// files are scanned line by line when `query`'s target is
+    /// [`SearchTarget::Contents`].
+    async fn search(
+        &self,
+        ipfs_path: &IpfsPath,
+        query: SearchQuery,
+    ) -> Result<LocalBoxStream<'static, Result<SearchMatch>>>
+    where
+        Self: Clone + 'static,
+    {
+        if ipfs_path.cid().is_none() {
+            return Err(anyhow!("IPFS path does not refer to a CID"));
+        }
+        let api = self.clone();
+        let ipfs_path = ipfs_path.clone();
+
+        let stream = async_stream::try_stream! {
+            let blocks = api.get_stream(&ipfs_path);
+            tokio::pin!(blocks);
+            let mut total_matches = 0usize;
+            'outer: while let Some(block) = blocks.next().await {
+                let (path, out) = block?;
+                match out {
+                    OutType::Dir | OutType::Symlink(_) => {
+                        if let Some(m) = search_path_name(&path, &query) {
+                            total_matches += 1;
+                            yield m;
+                        }
+                    }
+                    OutType::Reader(reader) => {
+                        if query.target() == SearchTarget::PathName {
+                            if let Some(m) = search_path_name(&path, &query) {
+                                total_matches += 1;
+                                yield m;
+                            }
+                            continue;
+                        }
+                        for m in search_contents(&path, &query, reader).await? {
+                            total_matches += 1;
+                            yield m;
+                            if query.max_total_matches().is_some_and(|max| total_matches >= max) {
+                                break 'outer;
+                            }
+                        }
+                    }
+                }
+                if query.max_total_matches().is_some_and(|max| total_matches >= max) {
+                    break;
+                }
+            }
+        };
+
+        Ok(Box::pin(stream))
+    }
+
+    /// Serialize `value` to canonical dag-cbor, store it as a CIDv1 block,
+    /// and return its CID.
+    async fn dag_put<T: Serialize>(&self, value: &T) -> Result<Cid> {
+        crate::dag::dag_put(self, value).await
+    }
+
+    /// Resolve `ipfs_path` to a dag-cbor node, following any remaining path
+    /// segments as map-key lookups, and deserialize it as `T`.
+    async fn dag_get<T: DeserializeOwned>(&self, ipfs_path: &IpfsPath) -> Result<T> {
+        crate::dag::dag_get(self, ipfs_path).await
+    }
+
+    /// The set of capabilities this `Api` provides.
+    ///
+    /// `ApiExt`'s own methods are implemented purely in terms of
+    /// `Api::get_stream`/`get_block`/`put_block`, so dag-cbor, content
+    /// search, directory wrapping, and resumable get are always available
+    /// regardless of backend. (FUSE mounting is gated separately by the
+    /// `fuse` feature, not advertised here.) A backend that additionally
+    /// wants to report fewer or different capabilities should override this
+    /// method directly on its concrete `Api` impl rather than through this
+    /// default. See `iroh_rpc_client::Config::negotiate` for how an RPC
+    /// client intersects what each of its configured services reports.
+    async fn capabilities(&self) -> Result<iroh_rpc_client::Capabilities> {
+        Ok(iroh_rpc_client::Capabilities::new([
+            iroh_rpc_client::Capability::DagCbor,
+            iroh_rpc_client::Capability::ContentSearch,
+            iroh_rpc_client::Capability::WrapDir,
+            iroh_rpc_client::Capability::ResumableGet,
+        ]))
+    }
 }
 
 impl<T> ApiExt for T where T: Api {}
@@ -68,6 +260,7 @@ impl<T> ApiExt for T where T: Api {}
 async fn save_get_stream(
     root_path: &Path,
     blocks: impl Stream<Item = Result<(RelativePathBuf, OutType)>>,
+    mode: GetMode,
 ) -> Result<()> {
     tokio::pin!(blocks);
     while let Some(block) = blocks.next().await {
@@ -81,13 +274,26 @@ async fn save_get_stream(
                 if let Some(parent) = path.parent() {
                     tokio::fs::create_dir_all(parent.to_path(root_path)).await?;
                 }
-                let mut f = tokio::fs::File::create(full_path).await?;
-                tokio::io::copy(&mut reader, &mut f).await?;
+                if mode == GetMode::Resume && full_path.exists() {
+                    resume_file(&full_path, &mut reader).await?;
+                } else {
+                    if mode == GetMode::Overwrite {
+                        remove_existing_entry(&full_path).await?;
+                    }
+                    let mut f = tokio::fs::File::create(full_path).await?;
+                    tokio::io::copy(&mut reader, &mut f).await?;
+                }
             }
             OutType::Symlink(target) => {
                 if let Some(parent) = path.parent() {
                     tokio::fs::create_dir_all(parent.to_path(root_path)).await?;
                 }
+                if mode == GetMode::Resume && full_path.exists() {
+                    continue;
+                }
+                if mode == GetMode::Overwrite {
+                    remove_existing_entry(&full_path).await?;
+                }
                 #[cfg(windows)]
                 tokio::task::spawn_blocking(move || {
                     make_windows_symlink(target, full_path).map_err(|e| anyhow::anyhow!(e))
@@ -102,6 +308,99 @@ async fn save_get_stream(
     Ok(())
 }
 
+/// Remove whatever is already at `full_path` (file, symlink, or directory),
+/// for `GetMode::Overwrite`. A missing entry is not an error.
+async fn remove_existing_entry(full_path: &Path) -> Result<()> {
+    let metadata = match tokio::fs::symlink_metadata(full_path).await {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    if metadata.is_dir() {
+        tokio::fs::remove_dir_all(full_path).await?;
+    } else {
+        tokio::fs::remove_file(full_path).await?;
+    }
+    Ok(())
+}
+
+/// Size of the chunks compared against the on-disk file when resuming a
+/// `get`. Cheap rather than exact: a hash of each chunk, not a full-file
+/// checksum.
+const RESUME_CHUNK_SIZE: usize = 64 * 1024;
+
+fn cheap_chunk_hash(bytes: &[u8]) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Read up to `buf.len()` bytes from `reader`, short-reading only on EOF.
+async fn fill_buf(reader: &mut (impl tokio::io::AsyncRead + Unpin), buf: &mut [u8]) -> Result<usize> {
+    use tokio::io::AsyncReadExt;
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+/// Resume writing `reader`'s contents into the existing file at `full_path`:
+/// walk it chunk by chunk, comparing each incoming chunk's hash against the
+/// matching chunk already on disk, and as soon as one diverges (or the
+/// existing file ends) seek there and append/overwrite the rest.
+///
+/// If every incoming chunk matches and the reader reaches EOF exactly where
+/// the existing file ends, the file is already complete and is left
+/// untouched.
+async fn resume_file(full_path: &Path, reader: &mut (impl tokio::io::AsyncRead + Unpin)) -> Result<()> {
+    use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+    let mut existing = tokio::fs::File::open(full_path).await?;
+    let existing_len = existing.metadata().await?.len();
+
+    let mut verified: u64 = 0;
+    let mut incoming = vec![0u8; RESUME_CHUNK_SIZE];
+    let mut on_disk = vec![0u8; RESUME_CHUNK_SIZE];
+
+    loop {
+        let n = fill_buf(reader, &mut incoming).await?;
+        if n == 0 {
+            // incoming DAG content is exhausted. If the on-disk file is
+            // longer than what we just verified, it has stale trailing
+            // bytes beyond the authoritative content and must be truncated.
+            if verified < existing_len {
+                let out = tokio::fs::OpenOptions::new().write(true).open(full_path).await?;
+                out.set_len(verified).await?;
+            }
+            return Ok(());
+        }
+
+        let matches = verified + n as u64 <= existing_len && {
+            let m = fill_buf(&mut existing, &mut on_disk[..n]).await?;
+            m == n && cheap_chunk_hash(&incoming[..n]) == cheap_chunk_hash(&on_disk[..n])
+        };
+        if matches {
+            verified += n as u64;
+            continue;
+        }
+
+        // either the existing file diverges here, or it simply ended:
+        // truncate to the last verified byte and append the rest.
+        let mut out = tokio::fs::OpenOptions::new().write(true).open(full_path).await?;
+        out.seek(std::io::SeekFrom::Start(verified)).await?;
+        out.set_len(verified).await?;
+        out.write_all(&incoming[..n]).await?;
+        tokio::io::copy(reader, &mut out).await?;
+        return Ok(());
+    }
+}
+
 #[cfg(windows)]
 fn make_windows_symlink(target: PathBuf, path: PathBuf) -> Result<()> {
     if target.is_dir() {
@@ -145,7 +444,7 @@ mod tests {
             )),
         ]));
         let tmp_dir = TempDir::new().unwrap().path().join("test_save_get_stream");
-        save_get_stream(&tmp_dir, stream).await.unwrap();
+        save_get_stream(&tmp_dir, stream, GetMode::Fresh).await.unwrap();
         assert!(tmp_dir.join("a").is_dir());
         assert!(tmp_dir.join("a/c").is_symlink());
         let target = tokio::fs::read_link(tmp_dir.join("a/c"))
@@ -155,6 +454,69 @@ mod tests {
         assert_eq!(std::fs::read_to_string(tmp_dir.join("b")).unwrap(), "hello");
     }
 
+    #[tokio::test]
+    async fn test_save_get_stream_overwrite_replaces_existing_file_with_symlink() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("c");
+        std::fs::write(&path, "stale file left behind by an earlier run").unwrap();
+
+        let stream = Box::pin(futures::stream::iter(vec![Ok((
+            RelativePathBuf::from_path("c").unwrap(),
+            OutType::Symlink(PathBuf::from("../b")),
+        ))]));
+        save_get_stream(tmp_dir.path(), stream, GetMode::Overwrite)
+            .await
+            .unwrap();
+
+        assert!(path.is_symlink());
+        let target = tokio::fs::read_link(&path).await.expect("file to exist");
+        assert_eq!(target, PathBuf::from("../b"));
+    }
+
+    #[tokio::test]
+    async fn test_resume_file_skips_completed_file() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("b");
+        std::fs::write(&path, "hello").unwrap();
+
+        let mut reader = std::io::Cursor::new("hello");
+        resume_file(&path, &mut reader).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_resume_file_appends_missing_tail() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("b");
+        std::fs::write(&path, "hello").unwrap();
+
+        let mut reader = std::io::Cursor::new("hello world");
+        resume_file(&path, &mut reader).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_resume_file_overwrites_diverged_content() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("b");
+        std::fs::write(&path, "goodbye").unwrap();
+
+        let mut reader = std::io::Cursor::new("hello");
+        resume_file(&path, &mut reader).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_resume_file_truncates_stale_trailing_bytes() {
+        let tmp_dir = TempDir::new().unwrap();
+        let path = tmp_dir.path().join("b");
+        std::fs::write(&path, "hello world").unwrap();
+
+        let mut reader = std::io::Cursor::new("hello");
+        resume_file(&path, &mut reader).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+    }
+
     #[test]
     fn test_get_root_path() {
         let ipfs_path =
@@ -0,0 +1,135 @@
+//! Typed IPLD dag-cbor put/get, for structured records rather than UnixFS
+//! file trees.
+//!
+//! `dag_put` serializes a value to canonical dag-cbor (map keys sorted, CID
+//! links encoded as tag 42 byte strings) and hashes the resulting block;
+//! `dag_get` reverses that, additionally resolving any path segments left in
+//! the `IpfsPath` as map-key lookups that may themselves bottom out in a
+//! link to another block.
+
+use anyhow::{anyhow, Context, Result};
+use libipld::cbor::DagCborCodec;
+use libipld::multihash::{Code, MultihashDigest};
+use libipld::prelude::Codec;
+use libipld::{Cid as LibipldCid, Ipld};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{Api, Cid, IpfsPath};
+
+/// dag-cbor's multicodec code, per the multicodec table.
+const DAG_CBOR_CODEC: u64 = 0x71;
+
+/// Serialize `value` to canonical dag-cbor, hash it with sha2-256, and store
+/// the resulting block through `api`, returning its CIDv1.
+pub async fn dag_put<A: Api, T: Serialize>(api: &A, value: &T) -> Result<Cid> {
+    let ipld = libipld::serde::to_ipld(value).context("failed to convert value to IPLD")?;
+    let bytes = DagCborCodec.encode(&ipld).context("failed to encode dag-cbor block")?;
+    let hash = Code::Sha2_256.digest(&bytes);
+    let cid = LibipldCid::new_v1(DAG_CBOR_CODEC, hash);
+    api.put_block(cid, bytes.into()).await?;
+    Ok(cid)
+}
+
+/// Resolve `ipfs_path`, decode the dag-cbor block it points to, walk any
+/// remaining path segments as map-key lookups (dereferencing links along
+/// the way), and deserialize the final node as `T`.
+pub async fn dag_get<A: Api, T: DeserializeOwned>(api: &A, ipfs_path: &IpfsPath) -> Result<T> {
+    let cid = ipfs_path
+        .cid()
+        .ok_or_else(|| anyhow!("IPFS path does not refer to a CID"))?;
+
+    let mut node = decode_block(api, *cid).await?;
+    for segment in ipfs_path.tail() {
+        node = resolve_segment(api, node, segment).await?;
+    }
+
+    libipld::serde::from_ipld(node).context("failed to deserialize resolved node")
+}
+
+async fn decode_block<A: Api>(api: &A, cid: Cid) -> Result<Ipld> {
+    let bytes = api.get_block(cid).await?;
+    DagCborCodec
+        .decode(&bytes)
+        .context("failed to decode dag-cbor block")
+}
+
+/// Descend one `segment` into `node`: a map lookup by key, or a list index
+/// by parsed number. Pulled out of `resolve_segment` so this pure logic can
+/// be unit tested without needing a real `Api`.
+fn step(node: Ipld, segment: &str) -> Result<Ipld> {
+    match node {
+        Ipld::Map(mut map) => map
+            .remove(segment)
+            .ok_or_else(|| anyhow!("no such key {segment:?} in dag-cbor map")),
+        Ipld::List(mut list) => {
+            let index: usize = segment
+                .parse()
+                .with_context(|| format!("{segment:?} is not a valid list index"))?;
+            if index >= list.len() {
+                return Err(anyhow!("index {index} out of bounds"));
+            }
+            Ok(list.swap_remove(index))
+        }
+        other => Err(anyhow!("cannot descend into {other:?} with {segment:?}")),
+    }
+}
+
+/// Step one `segment` deeper into `node`, following a link if the segment
+/// resolves to one.
+async fn resolve_segment<A: Api>(api: &A, node: Ipld, segment: &str) -> Result<Ipld> {
+    match step(node, segment)? {
+        Ipld::Link(cid) => decode_block(api, cid).await,
+        other => Ok(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn map(entries: impl IntoIterator<Item = (&'static str, Ipld)>) -> Ipld {
+        Ipld::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn test_step_map_lookup() {
+        let node = map([("a", Ipld::Integer(1)), ("b", Ipld::Integer(2))]);
+        assert_eq!(step(node, "b").unwrap(), Ipld::Integer(2));
+    }
+
+    #[test]
+    fn test_step_missing_key() {
+        let node = map([("a", Ipld::Integer(1))]);
+        assert!(step(node, "missing").is_err());
+    }
+
+    #[test]
+    fn test_step_list_index() {
+        let node = Ipld::List(vec![Ipld::Integer(10), Ipld::Integer(20)]);
+        assert_eq!(step(node, "1").unwrap(), Ipld::Integer(20));
+    }
+
+    #[test]
+    fn test_step_list_out_of_bounds() {
+        let node = Ipld::List(vec![Ipld::Integer(10)]);
+        assert!(step(node, "5").is_err());
+    }
+
+    #[test]
+    fn test_step_list_invalid_index() {
+        let node = Ipld::List(vec![Ipld::Integer(10)]);
+        assert!(step(node, "not-a-number").is_err());
+    }
+
+    #[test]
+    fn test_step_into_scalar_errors() {
+        let node = Ipld::Integer(42);
+        assert!(step(node, "a").is_err());
+    }
+}
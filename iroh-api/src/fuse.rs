@@ -0,0 +1,461 @@
+//! Read-only FUSE mount of a UnixFS DAG.
+//!
+//! Unlike `ApiExt::get`, which walks `get_stream` and materializes every
+//! block to disk up front, `IpfsFuse` assigns inodes to directory entries as
+//! they're discovered and only fetches the blocks covering the byte range a
+//! `read` actually asks for. This lets a caller browse a huge dataset without
+//! downloading the contents of files they never read. Listing a directory
+//! still walks its whole subtree (see `IpfsFuse::ensure_children`).
+#![cfg(all(unix, feature = "fuse"))]
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory,
+    ReplyEmpty, ReplyEntry, ReplyOpen, Request as FuseRequest,
+};
+use relative_path::RelativePathBuf;
+use tokio::runtime::Handle;
+
+use crate::{Api, IpfsPath, OutType};
+
+const TTL: Duration = Duration::from_secs(1);
+const ROOT_INO: u64 = 1;
+
+/// Whatever `OutType::Reader` wraps, object-safe so it can be cached behind
+/// a single field across the life of an open file handle.
+trait ReaderSeek: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin {}
+impl<T: tokio::io::AsyncRead + tokio::io::AsyncSeek + Unpin> ReaderSeek for T {}
+
+/// A lazily-discovered inode: either a directory (with a link to walk for
+/// its children), a file (fetched on demand by byte range), or a symlink.
+struct Inode {
+    path: RelativePathBuf,
+    parent: u64,
+    kind: FileType,
+    /// `None` until first queried (via `lookup`/`getattr`) for a regular
+    /// file; directories and symlinks always start out `Some(0)`. Listing a
+    /// directory's entries (`readdir`) never needs a file's size, so this
+    /// stays unresolved for files nobody has `stat`ed yet.
+    size: Option<u64>,
+    children: Option<Vec<(u64, RelativePathBuf, FileType)>>,
+    symlink_target: Option<std::path::PathBuf>,
+}
+
+/// An open regular file: a cached reader positioned at `pos`, reused across
+/// `read` calls on the same handle so sequential reads don't re-walk the DAG
+/// from scratch for every kernel callback.
+struct FileHandle {
+    reader: Box<dyn ReaderSeek>,
+    pos: u64,
+}
+
+/// Serves the UnixFS tree rooted at `root` as a read-only FUSE filesystem.
+///
+/// `Filesystem` callbacks are synchronous, so every call into `api` is
+/// bounced onto `handle` with `block_on`.
+pub struct IpfsFuse<A> {
+    api: A,
+    root: IpfsPath,
+    handle: Handle,
+    inodes: Mutex<HashMap<u64, Inode>>,
+    next_ino: Mutex<u64>,
+    handles: Mutex<HashMap<u64, FileHandle>>,
+    next_fh: Mutex<u64>,
+}
+
+impl<A> IpfsFuse<A>
+where
+    A: Api + Clone + Send + Sync + 'static,
+{
+    pub fn new(api: A, root: IpfsPath, handle: Handle) -> Self {
+        let mut inodes = HashMap::new();
+        inodes.insert(
+            ROOT_INO,
+            // `path` is always relative to `self.root`, never to `self.root`'s
+            // own tail, so the root inode's path is empty.
+            Inode {
+                path: RelativePathBuf::new(),
+                parent: ROOT_INO,
+                kind: FileType::Directory,
+                size: Some(0),
+                children: None,
+                symlink_target: None,
+            },
+        );
+        Self {
+            api,
+            root,
+            handle,
+            inodes: Mutex::new(inodes),
+            next_ino: Mutex::new(ROOT_INO + 1),
+            handles: Mutex::new(HashMap::new()),
+            next_fh: Mutex::new(1),
+        }
+    }
+
+    /// Mount `root` at `mountpoint`, blocking the calling thread until it is
+    /// unmounted.
+    pub fn mount(api: A, root: IpfsPath, mountpoint: &Path) -> Result<()> {
+        let handle = Handle::current();
+        let fs = Self::new(api, root, handle);
+        let options = vec![MountOption::RO, MountOption::FSName("iroh".to_string())];
+        fuser::mount2(fs, mountpoint, &options)
+            .with_context(|| format!("failed to mount {}", mountpoint.display()))
+    }
+
+    /// Resolve an inode's path (always relative to `self.root`) into the
+    /// `IpfsPath` that should be passed to `get_stream` to fetch it.
+    fn ipfs_path_for(&self, path: &RelativePathBuf) -> IpfsPath {
+        if path.as_str().is_empty() {
+            self.root.clone()
+        } else {
+            self.root.join(path.components())
+        }
+    }
+
+    fn attr_for(&self, ino: u64, inode: &Inode, size: u64) -> FileAttr {
+        FileAttr {
+            ino,
+            size,
+            blocks: (size + 511) / 512,
+            atime: std::time::UNIX_EPOCH,
+            mtime: std::time::UNIX_EPOCH,
+            ctime: std::time::UNIX_EPOCH,
+            crtime: std::time::UNIX_EPOCH,
+            kind: inode.kind,
+            perm: if inode.kind == FileType::Directory {
+                0o555
+            } else {
+                0o444
+            },
+            nlink: 1,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 512,
+            flags: 0,
+        }
+    }
+
+    /// Populate `children` for a directory inode by walking one level of the
+    /// UnixFS tree, assigning fresh inode numbers to anything not already
+    /// known.
+    ///
+    /// This still drives `Api::get_stream`, which walks the whole subtree
+    /// beneath `ino` and is filtered down to direct children client-side;
+    /// `Api` has no shallow, single-level listing primitive in this crate to
+    /// call instead. A directory with large or deep descendants will pay
+    /// for the full recursive walk just to populate its own listing.
+    fn ensure_children(&self, ino: u64) -> Result<()> {
+        let (needs_walk, path) = {
+            let inodes = self.inodes.lock().unwrap();
+            let inode = inodes.get(&ino).ok_or_else(|| anyhow!("unknown inode"))?;
+            (inode.children.is_none(), inode.path.clone())
+        };
+        if !needs_walk {
+            return Ok(());
+        }
+
+        let api = self.api.clone();
+        let ipfs_path = self.ipfs_path_for(&path);
+        // `get_stream` re-roots at `ipfs_path`, so the `rel` paths it yields
+        // are relative to `path`'s subtree, not to `self.root`. Rebase them
+        // onto `path` before storing, so every `Inode::path` stays in the
+        // same coordinate system (relative to `self.root`).
+        let entries = self.handle.block_on(async move {
+            use futures::StreamExt;
+
+            let mut stream = api.get_stream(&ipfs_path);
+            let mut entries = Vec::new();
+            while let Some(item) = stream.next().await {
+                let (rel, out) = item?;
+                let mut segments = rel.components();
+                let first = match segments.next() {
+                    Some(component) => component,
+                    None => continue, // the subtree root itself
+                };
+                if segments.next().is_some() {
+                    continue; // deeper than a direct child
+                }
+                let child_path = path.join(first);
+
+                // Don't resolve a file's size here: doing so would mean
+                // every `readdir` touches every file in the directory just
+                // to learn its length, even though `readdir` itself never
+                // reports size. Leave it unresolved until something that
+                // actually needs it (`lookup`/`getattr`) asks, via
+                // `ensure_size`.
+                let (kind, size, symlink_target) = match out {
+                    OutType::Dir => (FileType::Directory, Some(0), None),
+                    OutType::Symlink(target) => (FileType::Symlink, Some(0), Some(target)),
+                    OutType::Reader(_) => (FileType::RegularFile, None, None),
+                };
+                entries.push((child_path, kind, size, symlink_target));
+            }
+            Ok::<_, anyhow::Error>(entries)
+        })?;
+
+        let mut inodes = self.inodes.lock().unwrap();
+        let mut children = Vec::with_capacity(entries.len());
+        for (child_path, kind, size, symlink_target) in entries {
+            let child_ino = {
+                let mut next = self.next_ino.lock().unwrap();
+                let ino = *next;
+                *next += 1;
+                ino
+            };
+            inodes.insert(
+                child_ino,
+                Inode {
+                    path: child_path.clone(),
+                    parent: ino,
+                    kind,
+                    size,
+                    children: None,
+                    symlink_target,
+                },
+            );
+            children.push((child_ino, child_path, kind));
+        }
+        if let Some(inode) = inodes.get_mut(&ino) {
+            inode.children = Some(children);
+        }
+        Ok(())
+    }
+
+    /// Resolve and cache a regular file's size, fetching it only the first
+    /// time it's actually asked for (`lookup`/`getattr`), not while merely
+    /// listing the directory that contains it.
+    fn ensure_size(&self, ino: u64) -> Result<u64> {
+        let path = {
+            let inodes = self.inodes.lock().unwrap();
+            let inode = inodes.get(&ino).ok_or_else(|| anyhow!("unknown inode"))?;
+            match inode.size {
+                Some(size) => return Ok(size),
+                None => inode.path.clone(),
+            }
+        };
+
+        let api = self.api.clone();
+        let ipfs_path = self.ipfs_path_for(&path);
+        let size = self.handle.block_on(async move {
+            use futures::StreamExt;
+            use tokio::io::AsyncSeekExt;
+
+            let mut stream = api.get_stream(&ipfs_path);
+            while let Some(item) = stream.next().await {
+                let (rel, out) = item?;
+                if !rel.as_str().is_empty() {
+                    continue;
+                }
+                if let OutType::Reader(mut reader) = out {
+                    return Ok::<_, anyhow::Error>(reader.seek(std::io::SeekFrom::End(0)).await?);
+                }
+            }
+            Err(anyhow!("{} is not a file", path))
+        })?;
+
+        let mut inodes = self.inodes.lock().unwrap();
+        if let Some(inode) = inodes.get_mut(&ino) {
+            inode.size = Some(size);
+        }
+        Ok(size)
+    }
+}
+
+impl<A> Filesystem for IpfsFuse<A>
+where
+    A: Api + Clone + Send + Sync + 'static,
+{
+    fn lookup(&mut self, _req: &FuseRequest, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        if self.ensure_children(parent).is_err() {
+            reply.error(libc::EIO);
+            return;
+        }
+        let found = {
+            let inodes = self.inodes.lock().unwrap();
+            let parent_inode = match inodes.get(&parent) {
+                Some(inode) => inode,
+                None => return reply.error(libc::ENOENT),
+            };
+            parent_inode.children.as_ref().and_then(|children| {
+                children
+                    .iter()
+                    .find(|(_, path, _)| path.file_name() == name.to_str())
+                    .cloned()
+            })
+        };
+        let ino = match found {
+            Some((ino, _, _)) => ino,
+            None => return reply.error(libc::ENOENT),
+        };
+        let size = match self.ensure_size(ino) {
+            Ok(size) => size,
+            Err(_) => return reply.error(libc::EIO),
+        };
+        let inodes = self.inodes.lock().unwrap();
+        let inode = inodes.get(&ino).unwrap();
+        reply.entry(&TTL, &self.attr_for(ino, inode, size), 0);
+    }
+
+    fn getattr(&mut self, _req: &FuseRequest, ino: u64, reply: ReplyAttr) {
+        let size = match self.ensure_size(ino) {
+            Ok(size) => size,
+            Err(_) => return reply.error(libc::EIO),
+        };
+        let inodes = self.inodes.lock().unwrap();
+        match inodes.get(&ino) {
+            Some(inode) => reply.attr(&TTL, &self.attr_for(ino, inode, size)),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &FuseRequest,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        if self.ensure_children(ino).is_err() {
+            reply.error(libc::EIO);
+            return;
+        }
+        let inodes = self.inodes.lock().unwrap();
+        let inode = match inodes.get(&ino) {
+            Some(inode) => inode,
+            None => return reply.error(libc::ENOENT),
+        };
+        let parent = inode.parent;
+        let mut entries = vec![
+            (ino, FileType::Directory, ".".to_string()),
+            (parent, FileType::Directory, "..".to_string()),
+        ];
+        if let Some(children) = &inode.children {
+            for (child_ino, path, kind) in children {
+                entries.push((
+                    *child_ino,
+                    *kind,
+                    path.file_name().unwrap_or_default().to_string(),
+                ));
+            }
+        }
+        for (i, (ino, kind, name)) in entries.into_iter().enumerate().skip(offset as usize) {
+            if reply.add(ino, (i + 1) as i64, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+
+    fn open(&mut self, _req: &FuseRequest, ino: u64, _flags: i32, reply: ReplyOpen) {
+        let path = {
+            let inodes = self.inodes.lock().unwrap();
+            match inodes.get(&ino) {
+                Some(inode) if inode.kind == FileType::RegularFile => inode.path.clone(),
+                Some(_) => return reply.error(libc::EISDIR),
+                None => return reply.error(libc::ENOENT),
+            }
+        };
+
+        let api = self.api.clone();
+        let ipfs_path = self.ipfs_path_for(&path);
+        let reader = self.handle.block_on(async move {
+            use futures::StreamExt;
+            let mut stream = api.get_stream(&ipfs_path);
+            while let Some(item) = stream.next().await {
+                let (rel, out) = item?;
+                // `get_stream` is re-rooted at `path` here, so the entry for
+                // `path` itself comes back with an empty relative path.
+                if !rel.as_str().is_empty() {
+                    continue;
+                }
+                if let OutType::Reader(reader) = out {
+                    return Ok::<_, anyhow::Error>(Box::new(reader) as Box<dyn ReaderSeek>);
+                }
+            }
+            Err(anyhow!("{} is not a file", path))
+        });
+
+        match reader {
+            Ok(reader) => {
+                let fh = {
+                    let mut next = self.next_fh.lock().unwrap();
+                    let fh = *next;
+                    *next += 1;
+                    fh
+                };
+                self.handles
+                    .lock()
+                    .unwrap()
+                    .insert(fh, FileHandle { reader, pos: 0 });
+                reply.opened(fh, 0);
+            }
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn release(
+        &mut self,
+        _req: &FuseRequest,
+        _ino: u64,
+        fh: u64,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        _flush: bool,
+        reply: ReplyEmpty,
+    ) {
+        self.handles.lock().unwrap().remove(&fh);
+        reply.ok();
+    }
+
+    fn read(
+        &mut self,
+        _req: &FuseRequest,
+        _ino: u64,
+        fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let mut handles = self.handles.lock().unwrap();
+        let handle = match handles.get_mut(&fh) {
+            Some(handle) => handle,
+            None => return reply.error(libc::EBADF),
+        };
+
+        let result = self.handle.block_on(async {
+            use tokio::io::{AsyncReadExt, AsyncSeekExt};
+            if handle.pos != offset as u64 {
+                handle.reader.seek(std::io::SeekFrom::Start(offset as u64)).await?;
+            }
+            let mut buf = vec![0u8; size as usize];
+            let n = handle.reader.read(&mut buf).await?;
+            buf.truncate(n);
+            handle.pos = offset as u64 + n as u64;
+            Ok::<_, anyhow::Error>(buf)
+        });
+
+        match result {
+            Ok(buf) => reply.data(&buf),
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn readlink(&mut self, _req: &FuseRequest, ino: u64, reply: ReplyData) {
+        let inodes = self.inodes.lock().unwrap();
+        match inodes.get(&ino).and_then(|inode| inode.symlink_target.as_ref()) {
+            Some(target) => reply.data(target.to_string_lossy().as_bytes()),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Grep-like content search over a UnixFS DAG without writing it to disk.
+//!
+//! [`ApiExt::search`] walks the same `get_stream` used by `get`, but instead
+//! of materializing every block to a file it scans `OutType::Reader` entries
+//! in bounded chunks and streams back matches file by file: the matches of
+//! one file are yielded together once that whole file has been scanned.
+
+use std::path::PathBuf;
+
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+use relative_path::RelativePathBuf;
+
+/// What part of an entry a [`SearchQuery`] matches against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchTarget {
+    /// Match against file contents, line by line.
+    Contents,
+    /// Match against the entry's path within the DAG.
+    PathName,
+}
+
+/// A content or path-name search over a retrieved DAG.
+#[derive(Debug, Clone)]
+pub struct SearchQuery {
+    pattern: Regex,
+    path_glob: Option<GlobMatcher>,
+    target: SearchTarget,
+    max_matches_per_file: Option<usize>,
+    max_total_matches: Option<usize>,
+}
+
+impl SearchQuery {
+    /// Build a query from a regex pattern (use [`regex::escape`] first for a
+    /// literal search).
+    pub fn new(pattern: &str, target: SearchTarget) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: Regex::new(pattern)?,
+            path_glob: None,
+            target,
+            max_matches_per_file: None,
+            max_total_matches: None,
+        })
+    }
+
+    /// Restrict the search to entries whose path matches `glob`.
+    pub fn with_path_glob(mut self, glob: &str) -> Result<Self, globset::Error> {
+        self.path_glob = Some(Glob::new(glob)?.compile_matcher());
+        Ok(self)
+    }
+
+    pub fn with_max_matches_per_file(mut self, max: usize) -> Self {
+        self.max_matches_per_file = Some(max);
+        self
+    }
+
+    pub fn with_max_total_matches(mut self, max: usize) -> Self {
+        self.max_total_matches = Some(max);
+        self
+    }
+
+    pub fn target(&self) -> SearchTarget {
+        self.target
+    }
+
+    pub fn max_total_matches(&self) -> Option<usize> {
+        self.max_total_matches
+    }
+
+    pub(crate) fn path_allowed(&self, path: &RelativePathBuf) -> bool {
+        match &self.path_glob {
+            Some(glob) => glob.is_match(path.as_str()),
+            None => true,
+        }
+    }
+}
+
+/// A single match produced by [`ApiExt::search`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub path: PathBuf,
+    /// `None` for path-name matches, which have no line to point to.
+    pub line_number: Option<usize>,
+    pub line: String,
+    pub submatches: Vec<(usize, usize)>,
+}
+
+/// Scan bytes read line-by-line from `reader` for matches against `query`,
+/// honoring the per-file match limit.
+pub(crate) async fn search_contents(
+    path: &RelativePathBuf,
+    query: &SearchQuery,
+    mut reader: std::pin::Pin<Box<dyn tokio::io::AsyncRead>>,
+) -> std::io::Result<Vec<SearchMatch>> {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    if !query.path_allowed(path) {
+        return Ok(Vec::new());
+    }
+
+    let mut lines = BufReader::new(&mut reader).lines();
+    let mut matches = Vec::new();
+    let mut line_number = 0;
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if let Some(max) = query.max_matches_per_file {
+            if matches.len() >= max {
+                break;
+            }
+        }
+        let submatches: Vec<(usize, usize)> = query
+            .pattern
+            .find_iter(&line)
+            .map(|m| (m.start(), m.end()))
+            .collect();
+        if !submatches.is_empty() {
+            matches.push(SearchMatch {
+                path: path.to_path("."),
+                line_number: Some(line_number),
+                line,
+                submatches,
+            });
+        }
+    }
+    Ok(matches)
+}
+
+/// Check whether `path`'s name (not its contents) matches `query`, used for
+/// directories, symlinks, and `SearchTarget::PathName` queries.
+pub(crate) fn search_path_name(path: &RelativePathBuf, query: &SearchQuery) -> Option<SearchMatch> {
+    if !query.path_allowed(path) {
+        return None;
+    }
+    let name = path.as_str();
+    let submatches: Vec<(usize, usize)> = query
+        .pattern
+        .find_iter(name)
+        .map(|m| (m.start(), m.end()))
+        .collect();
+    if submatches.is_empty() {
+        return None;
+    }
+    Some(SearchMatch {
+        path: path.to_path("."),
+        line_number: None,
+        line: name.to_string(),
+        submatches,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reader(contents: &'static str) -> std::pin::Pin<Box<dyn tokio::io::AsyncRead>> {
+        Box::pin(std::io::Cursor::new(contents))
+    }
+
+    #[tokio::test]
+    async fn test_search_contents_finds_matches() {
+        let query = SearchQuery::new("world", SearchTarget::Contents).unwrap();
+        let path = RelativePathBuf::from_path("a/b.txt").unwrap();
+        let matches = search_contents(&path, &query, reader("hello\nworld\nfoo world bar"))
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].line_number, Some(2));
+        assert_eq!(matches[1].line_number, Some(3));
+        assert_eq!(matches[1].submatches, vec![(4, 9)]);
+    }
+
+    #[tokio::test]
+    async fn test_search_contents_respects_path_glob() {
+        let query = SearchQuery::new("world", SearchTarget::Contents)
+            .unwrap()
+            .with_path_glob("*.rs")
+            .unwrap();
+        let path = RelativePathBuf::from_path("a/b.txt").unwrap();
+        let matches = search_contents(&path, &query, reader("hello world"))
+            .await
+            .unwrap();
+        assert!(matches.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_contents_respects_max_matches_per_file() {
+        let query = SearchQuery::new("o", SearchTarget::Contents)
+            .unwrap()
+            .with_max_matches_per_file(1);
+        let path = RelativePathBuf::from_path("a/b.txt").unwrap();
+        let matches = search_contents(&path, &query, reader("foo\nbar\nbaz\nboo"))
+            .await
+            .unwrap();
+        assert_eq!(matches.len(), 1);
+    }
+
+    #[test]
+    fn test_search_path_name_matches_and_filters() {
+        let query = SearchQuery::new("b$", SearchTarget::PathName).unwrap();
+        let matching = RelativePathBuf::from_path("a/b").unwrap();
+        let non_matching = RelativePathBuf::from_path("a/c").unwrap();
+        assert!(search_path_name(&matching, &query).is_some());
+        assert!(search_path_name(&non_matching, &query).is_none());
+    }
+
+    #[test]
+    fn test_search_path_name_respects_path_glob() {
+        let query = SearchQuery::new("b", SearchTarget::PathName)
+            .unwrap()
+            .with_path_glob("*.rs")
+            .unwrap();
+        let path = RelativePathBuf::from_path("a/b.txt").unwrap();
+        assert!(search_path_name(&path, &query).is_none());
+    }
+}
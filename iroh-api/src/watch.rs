@@ -0,0 +1,93 @@
+//! Filesystem watching support for `ApiExt::add_watch`.
+//!
+//! Raw `notify` events arrive in bursts (an editor saving a file often
+//! produces several in a row), so they're coalesced per-path into a single
+//! debounced [`ChangeKind`] before triggering a re-add.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+/// The kind of change observed for a watched path, after debouncing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+/// A debounced filesystem change ready to be re-added (or removed).
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+}
+
+/// How long the watcher must go without any raw event (for any path) before
+/// every pending change is flushed at once.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watches `root` recursively and yields debounced [`Change`]s on `rx`.
+///
+/// The returned `RecommendedWatcher` must be kept alive for as long as
+/// watching should continue; dropping it stops the watch.
+pub fn watch(root: &Path) -> Result<(RecommendedWatcher, mpsc::UnboundedReceiver<Change>)> {
+    let (raw_tx, mut raw_rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            let _ = raw_tx.send(event);
+        }
+    })?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut pending: HashMap<PathBuf, ChangeKind> = HashMap::new();
+        loop {
+            let event = tokio::select! {
+                event = raw_rx.recv() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                _ = tokio::time::sleep(DEBOUNCE), if !pending.is_empty() => {
+                    for (path, kind) in pending.drain() {
+                        if tx.send(Change { path, kind }).is_err() {
+                            return;
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            let kind = match event.kind {
+                notify::EventKind::Create(_) => ChangeKind::Created,
+                notify::EventKind::Modify(notify::event::ModifyKind::Name(_)) => {
+                    ChangeKind::Renamed
+                }
+                notify::EventKind::Modify(_) => ChangeKind::Modified,
+                notify::EventKind::Remove(_) => ChangeKind::Removed,
+                notify::EventKind::Access(_) | notify::EventKind::Other | notify::EventKind::Any => {
+                    continue
+                }
+            };
+            for path in event.paths {
+                // A later event for the same path wins, except Removed
+                // always takes precedence so a quick create-then-delete
+                // doesn't surface as a spurious add.
+                let entry = pending.entry(path).or_insert(kind);
+                if kind == ChangeKind::Removed {
+                    *entry = ChangeKind::Removed;
+                } else if *entry != ChangeKind::Removed {
+                    *entry = kind;
+                }
+            }
+        }
+    });
+
+    Ok((watcher, rx))
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+/// A named feature flag a backend advertises support for.
+///
+/// New capabilities should be added here rather than invented ad hoc at the
+/// call site, so `Config::negotiate` has a single source of truth to
+/// intersect against.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Capability {
+    DagCbor,
+    FuseMount,
+    ContentSearch,
+    WrapDir,
+    ResumableGet,
+}
+
+impl Capability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::DagCbor => "dag-cbor",
+            Capability::FuseMount => "fuse-mount",
+            Capability::ContentSearch => "content-search",
+            Capability::WrapDir => "wrap-dir",
+            Capability::ResumableGet => "resumable-get",
+        }
+    }
+}
+
+/// The set of capabilities a single backend (gateway, p2p, or store
+/// service) reports supporting.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities(BTreeSet<Capability>);
+
+impl Capabilities {
+    pub fn new(caps: impl IntoIterator<Item = Capability>) -> Self {
+        Self(caps.into_iter().collect())
+    }
+
+    pub fn supports(&self, cap: Capability) -> bool {
+        self.0.contains(&cap)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Capability> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Capabilities supported by every one of `self` and `other`.
+    pub fn intersect(&self, other: &Capabilities) -> Capabilities {
+        Capabilities(self.0.intersection(&other.0).copied().collect())
+    }
+}
+
+impl FromIterator<Capability> for Capabilities {
+    fn from_iter<I: IntoIterator<Item = Capability>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intersect() {
+        let gateway = Capabilities::new([Capability::DagCbor, Capability::ContentSearch]);
+        let p2p = Capabilities::new([Capability::ContentSearch, Capability::FuseMount]);
+        let negotiated = gateway.intersect(&p2p);
+        assert!(negotiated.supports(Capability::ContentSearch));
+        assert!(!negotiated.supports(Capability::DagCbor));
+        assert!(!negotiated.supports(Capability::FuseMount));
+    }
+}
@@ -1,8 +1,12 @@
+use anyhow::Result;
 use config::{ConfigError, Map, Source, Value};
 use iroh_rpc_types::{gateway::GatewayClientAddr, p2p::P2pClientAddr, store::StoreClientAddr};
 use iroh_util::insert_into_config_map;
 use serde::{Deserialize, Serialize};
 
+use crate::capabilities::Capabilities;
+use crate::Client;
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
 // Config for the rpc Client
 pub struct Config {
@@ -48,6 +52,43 @@ impl Config {
             channels: Some(16),
         }
     }
+
+    /// Connect to every service this config names and intersect their
+    /// reported [`Capabilities`], so callers can fail fast with a clear
+    /// message instead of attempting an operation an unsupported transport
+    /// doesn't implement.
+    ///
+    /// A service that isn't configured simply doesn't contribute to the
+    /// intersection; a service that is configured but unreachable is an
+    /// error, since a caller explicitly asked to talk to it.
+    pub async fn negotiate(&self) -> Result<Capabilities> {
+        let client = Client::new(self.clone()).await?;
+        let mut negotiated: Option<Capabilities> = None;
+
+        if self.gateway_addr.is_some() {
+            let caps = client.gateway().capabilities().await?;
+            negotiated = Some(match negotiated {
+                Some(acc) => acc.intersect(&caps),
+                None => caps,
+            });
+        }
+        if self.p2p_addr.is_some() {
+            let caps = client.p2p().capabilities().await?;
+            negotiated = Some(match negotiated {
+                Some(acc) => acc.intersect(&caps),
+                None => caps,
+            });
+        }
+        if self.store_addr.is_some() {
+            let caps = client.store().capabilities().await?;
+            negotiated = Some(match negotiated {
+                Some(acc) => acc.intersect(&caps),
+                None => caps,
+            });
+        }
+
+        Ok(negotiated.unwrap_or_default())
+    }
 }
 
 #[cfg(test)]